@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use axum::http::{header::AUTHORIZATION, HeaderMap};
+
+use crate::GeoError;
+
+/// Who's making the request. `Anonymous` carries no identity of its own, so
+/// callers key rate limiting on the client IP instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Identity {
+    Anonymous,
+    ApiKey(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub identity: Identity,
+    pub max_requests_per_day: usize,
+}
+
+/// Pluggable request authentication. The default [`ApiKeyAuth`] checks a
+/// bearer token or `X-API-Key` header against a configured key set.
+#[async_trait]
+pub trait ApiAuth: Send + Sync {
+    async fn authenticate(&self, headers: &HeaderMap) -> Result<Principal, GeoError>;
+}
+
+/// Validates a bearer token / `X-API-Key` header against a configured set of
+/// keys, each carrying its own daily quota. Requests with no credentials at
+/// all are treated as anonymous rather than rejected; only a credential that
+/// doesn't match a configured key is unauthorized.
+pub struct ApiKeyAuth {
+    keys: HashMap<String, Principal>,
+    anonymous_max_requests_per_day: usize,
+}
+
+impl ApiKeyAuth {
+    pub fn new(keys: HashMap<String, Principal>, anonymous_max_requests_per_day: usize) -> Self {
+        Self {
+            keys,
+            anonymous_max_requests_per_day,
+        }
+    }
+}
+
+#[async_trait]
+impl ApiAuth for ApiKeyAuth {
+    async fn authenticate(&self, headers: &HeaderMap) -> Result<Principal, GeoError> {
+        let credential = bearer_token(headers).or_else(|| api_key_header(headers));
+
+        let Some(credential) = credential else {
+            return Ok(Principal {
+                identity: Identity::Anonymous,
+                max_requests_per_day: self.anonymous_max_requests_per_day,
+            });
+        };
+
+        self.keys
+            .get(&credential)
+            .cloned()
+            .ok_or_else(|| GeoError::Unauthorized("invalid API key".to_string()))
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_string)
+}
+
+fn api_key_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-api-key")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Parses the `API_KEYS` env var: comma-separated `key=name:quota` entries,
+/// e.g. `API_KEYS=abc123=alice:500,def456=bob:100`.
+pub fn parse_api_keys(raw: &str) -> HashMap<String, Principal> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let (key, rest) = entry.split_once('=')?;
+            let (name, quota) = rest.split_once(':')?;
+            let quota: usize = quota.parse().ok()?;
+
+            Some((
+                key.to_string(),
+                Principal {
+                    identity: Identity::ApiKey(name.to_string()),
+                    max_requests_per_day: quota,
+                },
+            ))
+        })
+        .collect()
+}
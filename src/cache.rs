@@ -0,0 +1,102 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    net::IpAddr,
+    time::Duration,
+};
+
+use moka::future::Cache;
+
+use crate::{GeoRequest, LocationResponse};
+
+/// A stable hash of the request's WiFi fingerprint (MAC + signal strength
+/// pairs, order-independent) plus `considerIp`. Keying on content instead of
+/// client IP lets two different clients reporting the same APs share a hit,
+/// and means a moving client whose fingerprint changed doesn't get served a
+/// stale answer.
+///
+/// When there's no WiFi fingerprint at all, the lookup is resolved purely
+/// from the client's IP, so the resolved IP is folded into the key too —
+/// otherwise every such request hashes identically and the first caller's
+/// location would be cached and served back to every other anonymous client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey(u64);
+
+impl CacheKey {
+    pub fn new(req: &GeoRequest, client_ip: IpAddr) -> Self {
+        let mut access_points: Vec<(&str, i32)> = req
+            .wifi_access_points
+            .iter()
+            .map(|ap| (ap.mac_address.as_str(), ap.signal_strength))
+            .collect();
+        access_points.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        access_points.hash(&mut hasher);
+        req.consider_ip.hash(&mut hasher);
+
+        if req.wifi_access_points.is_empty() {
+            client_ip.hash(&mut hasher);
+        }
+
+        Self(hasher.finish())
+    }
+}
+
+pub type CacheStore = Cache<CacheKey, LocationResponse>;
+
+pub fn build_cache_store(max_capacity: u64, time_to_live: Duration) -> CacheStore {
+    Cache::builder()
+        .max_capacity(max_capacity)
+        .time_to_live(time_to_live)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+    use crate::WifiAccessPoint;
+
+    fn req(wifi_access_points: Vec<WifiAccessPoint>, consider_ip: bool) -> GeoRequest {
+        GeoRequest {
+            consider_ip,
+            wifi_access_points,
+        }
+    }
+
+    fn ap(mac_address: &str, signal_strength: i32) -> WifiAccessPoint {
+        WifiAccessPoint {
+            mac_address: mac_address.to_string(),
+            signal_strength,
+        }
+    }
+
+    #[test]
+    fn same_wifi_fingerprint_ignores_client_ip() {
+        let request = req(vec![ap("aa:bb", -50), ap("cc:dd", -60)], true);
+        let ip_a = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let ip_b = IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8));
+
+        assert_eq!(CacheKey::new(&request, ip_a), CacheKey::new(&request, ip_b));
+    }
+
+    #[test]
+    fn wifi_fingerprint_order_does_not_matter() {
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let in_order = req(vec![ap("aa:bb", -50), ap("cc:dd", -60)], true);
+        let reordered = req(vec![ap("cc:dd", -60), ap("aa:bb", -50)], true);
+
+        assert_eq!(CacheKey::new(&in_order, ip), CacheKey::new(&reordered, ip));
+    }
+
+    #[test]
+    fn ip_only_lookup_folds_client_ip_into_key() {
+        let request = req(vec![], true);
+        let ip_a = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        let ip_b = IpAddr::V4(Ipv4Addr::new(5, 6, 7, 8));
+
+        assert_ne!(CacheKey::new(&request, ip_a), CacheKey::new(&request, ip_b));
+    }
+}
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use gdal::Dataset;
+use tokio::sync::Mutex;
+
+use crate::GeoError;
+
+/// Samples terrain elevation from a Digital Elevation Model raster (a
+/// GeoTIFF opened via GDAL). The dataset is opened once at startup and
+/// reused for every lookup; since the underlying GDAL handle isn't safe to
+/// touch concurrently, access is serialized behind a mutex and each lookup
+/// runs on the blocking thread pool.
+pub struct ElevationService {
+    dataset: Arc<Mutex<Dataset>>,
+}
+
+impl ElevationService {
+    pub fn open(dem_path: &str) -> Result<Self, GeoError> {
+        let dataset = Dataset::open(dem_path)
+            .map_err(|e| GeoError::Internal(format!("failed to open DEM at {dem_path}: {e}")))?;
+
+        Ok(Self {
+            dataset: Arc::new(Mutex::new(dataset)),
+        })
+    }
+
+    /// Returns the elevation in meters at `(lat, lon)`, or `None` if the
+    /// pixel falls outside the raster or is marked as nodata.
+    pub async fn elevation_at(&self, lat: f64, lon: f64) -> Result<Option<f64>, GeoError> {
+        let dataset = Arc::clone(&self.dataset);
+
+        tokio::task::spawn_blocking(move || {
+            let dataset = dataset.blocking_lock();
+            sample_elevation(&dataset, lat, lon)
+        })
+        .await
+        .map_err(|e| GeoError::Internal(format!("elevation lookup task panicked: {e}")))?
+    }
+}
+
+fn sample_elevation(dataset: &Dataset, lat: f64, lon: f64) -> Result<Option<f64>, GeoError> {
+    let geo_transform = dataset
+        .geo_transform()
+        .map_err(|e| GeoError::Internal(format!("failed to read DEM geotransform: {e}")))?;
+    let inverse = gdal::GeoTransformEx::invert(&geo_transform)
+        .map_err(|e| GeoError::Internal(format!("DEM geotransform is not invertible: {e}")))?;
+
+    let (pixel, line) = inverse.apply(lon, lat);
+    let (pixel, line) = (pixel.floor() as isize, line.floor() as isize);
+
+    let band = dataset
+        .rasterband(1)
+        .map_err(|e| GeoError::Internal(format!("failed to read DEM band 1: {e}")))?;
+
+    if pixel < 0 || line < 0 || pixel >= band.size().0 as isize || line >= band.size().1 as isize {
+        return Ok(None);
+    }
+
+    let buffer = band
+        .read_as::<f64>((pixel, line), (1, 1), (1, 1), None)
+        .map_err(|e| GeoError::Internal(format!("failed to sample DEM pixel: {e}")))?;
+    let value = buffer.data()[0];
+
+    match band.no_data_value() {
+        Some(nodata) if value == nodata => Ok(None),
+        _ => Ok(Some(value)),
+    }
+}
@@ -1,19 +1,32 @@
+mod auth;
+mod cache;
+#[cfg(feature = "elevation")]
+mod elevation;
+mod net;
+mod providers;
+mod storage;
+
 use axum::{
-    extract::{ConnectInfo, Json},
-    http::StatusCode,
-    routing::post,
+    extract::{ConnectInfo, Json, Path, Query},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
     Extension, Router,
 };
 use chrono::{Duration, Utc};
 use dashmap::DashMap;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::{env, net::SocketAddr, sync::Arc};
+use std::{env, net::{IpAddr, SocketAddr}, sync::Arc};
 use thiserror::Error;
-use tower_http::trace::TraceLayer;
+use tower_http::{compression::CompressionLayer, limit::RequestBodyLimitLayer, trace::TraceLayer};
 use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use auth::{ApiAuth, ApiKeyAuth, Identity};
+use cache::{build_cache_store, CacheKey, CacheStore};
+use net::{parse_trusted_proxies, resolve_client_ip};
+use providers::{GeoProvider, GoogleProvider, IpGeoProvider};
+use storage::{parse_flexible_timestamp, LocationPoint, LocationStore, MemoryLocationStore, SqlLocationStore};
+
 #[derive(Debug, Deserialize, Serialize)]
 struct GeoRequest {
     #[serde(rename = "considerIp")]
@@ -32,40 +45,38 @@ struct WifiAccessPoint {
     signal_strength: i32,
 }
 
-#[derive(Debug, Deserialize)]
-struct GoogleGeoResponse {
-    location: GoogleLocation,
-    accuracy: f64,
-}
-
-#[derive(Debug, Deserialize)]
-struct GoogleLocation {
-    lat: f64,
-    lng: f64,
-}
-
 #[derive(Debug, Serialize, Clone)]
 struct LocationResponse {
     lat: f64,
     lon: f64,
-}
-
-#[derive(Debug, Clone)]
-struct CacheEntry {
-    response: LocationResponse,
-    timestamp: chrono::DateTime<Utc>,
+    accuracy: Option<f64>,
+    /// Terrain elevation in meters, sampled from a local DEM raster. Always
+    /// `None` unless built with the `elevation` feature and `DEM_PATH` set.
+    elevation: Option<f64>,
 }
 
 type RateLimitStore = Arc<DashMap<String, Vec<chrono::DateTime<Utc>>>>;
-type CacheStore = Arc<DashMap<String, CacheEntry>>;
 
 #[derive(Clone)]
 struct AppConfig {
-    cache_ttl: Duration,
-    max_requests_per_day: usize,
-    google_api_key: String,
+    max_wifi_access_points: usize,
+    trusted_proxies: Vec<IpAddr>,
+    providers: Vec<Arc<dyn GeoProvider>>,
+    location_store: Arc<dyn LocationStore>,
+    auth: Arc<dyn ApiAuth>,
+    #[cfg(feature = "elevation")]
+    elevation_service: Option<Arc<elevation::ElevationService>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
 }
 
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
 #[derive(Error, Debug)]
 enum GeoError {
     #[error("Rate limit exceeded")]
@@ -74,14 +85,22 @@ enum GeoError {
     GoogleApi(String),
     #[error("Internal error: {0}")]
     Internal(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
 }
 
-impl Into<(StatusCode, String)> for GeoError {
-    fn into(self) -> (StatusCode, String) {
-        match self {
-            GeoError::RateLimited => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
+impl From<GeoError> for (StatusCode, String) {
+    fn from(err: GeoError) -> Self {
+        let message = err.to_string();
+
+        match err {
+            GeoError::RateLimited => (StatusCode::TOO_MANY_REQUESTS, message),
             GeoError::GoogleApi(e) => (StatusCode::BAD_GATEWAY, e),
             GeoError::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
+            GeoError::Unauthorized(e) => (StatusCode::UNAUTHORIZED, e),
+            GeoError::PayloadTooLarge(e) => (StatusCode::PAYLOAD_TOO_LARGE, e),
         }
     }
 }
@@ -91,71 +110,159 @@ async fn handle_geo(
     Extension(rate_limit_store): Extension<RateLimitStore>,
     Extension(cache_store): Extension<CacheStore>,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<GeoRequest>,
 ) -> Result<Json<LocationResponse>, (StatusCode, String)> {
-    let ip = addr.ip().to_string();
+    if payload.wifi_access_points.len() > config.max_wifi_access_points {
+        return Err(GeoError::PayloadTooLarge(format!(
+            "wifiAccessPoints exceeds the limit of {}",
+            config.max_wifi_access_points
+        ))
+        .into());
+    }
+
+    let client_ip = resolve_client_ip(addr, &headers, &config.trusted_proxies);
+    let ip = client_ip.to_string();
     let now = Utc::now();
+    let cache_key = CacheKey::new(&payload, client_ip);
+
+    // Authenticate before touching the cache — a cache hit must not let an
+    // invalid/unauthorized credential skip the 401. Cache hits are served
+    // before the rate-limit check and don't count toward the daily quota.
+    let principal = config
+        .auth
+        .authenticate(&headers)
+        .await
+        .map_err(Into::<(StatusCode, String)>::into)?;
 
-    //  cache check
-    if let Some(entry) = cache_store.get(&ip) {
-        if entry.timestamp + config.cache_ttl > now {
-            info!(%ip, "cache hit");
-            return Ok(Json(entry.response.clone()));
-        }
+    // cache check
+    if let Some(response) = cache_store.get(&cache_key).await {
+        info!(%ip, "cache hit");
+        return Ok(Json(response));
     }
 
+    let (rate_limit_key, max_requests_per_day) = match &principal.identity {
+        Identity::Anonymous => (format!("ip:{ip}"), principal.max_requests_per_day),
+        Identity::ApiKey(id) => (format!("key:{id}"), principal.max_requests_per_day),
+    };
+
     // rate limiting check
-    let mut entry = rate_limit_store.entry(ip.clone()).or_default();
+    let mut entry = rate_limit_store.entry(rate_limit_key.clone()).or_default();
     entry.retain(|t| *t + Duration::days(1) > now);
 
-    if entry.len() >= config.max_requests_per_day {
-        error!(%ip, "rate limit exceeded");
+    if entry.len() >= max_requests_per_day {
+        error!(%ip, key = %rate_limit_key, "rate limit exceeded");
         return Err(GeoError::RateLimited.into());
     }
 
     entry.push(now);
 
-    info!(%ip, ?payload, "calling Google API");
+    info!(%ip, ?payload, "resolving location");
 
-    let url = format!(
-        "https://www.googleapis.com/geolocation/v1/geolocate?key={}",
-        config.google_api_key
-    );
+    // If there's no WiFi fingerprint to work with, the only thing left to
+    // try is an IP-based provider, and only if the caller said that's okay.
+    let candidates: Vec<&Arc<dyn GeoProvider>> = if payload.wifi_access_points.is_empty() {
+        if !payload.consider_ip {
+            return Err(GeoError::Internal(
+                "no wifi access points provided and considerIp is false".to_string(),
+            )
+            .into());
+        }
+        config.providers.iter().filter(|p| !p.requires_wifi()).collect()
+    } else {
+        // Even with a WiFi fingerprint, a wifi-based provider can fail and
+        // fall through to an IP-based one — only allow that fallback if the
+        // caller opted into IP-based location.
+        config
+            .providers
+            .iter()
+            .filter(|p| p.requires_wifi() || payload.consider_ip)
+            .collect()
+    };
 
-    let client = Client::new();
-    let resp = client.post(&url).json(&payload).send().await.map_err(|e| {
-        error!(%ip, error = ?e, "request failed");
-        GeoError::Internal(e.to_string()).into()
-    })?;
-
-    if resp.status().is_success() {
-        let geo: GoogleGeoResponse = resp.json().await.map_err(|e| {
-            error!(%ip, error = ?e, "json decode failed");
-            GeoError::Internal(e.to_string()).into()
-        })?;
-
-        info!(%ip, lat = geo.location.lat, lon = geo.location.lng, accuracy = geo.accuracy, "success");
-
-        let response = LocationResponse {
-            lat: geo.location.lat,
-            lon: geo.location.lng,
-        };
-
-        //  update cache
-        cache_store.insert(
-            ip.clone(),
-            CacheEntry {
-                response: response.clone(),
-                timestamp: now,
-            },
-        );
+    let mut last_err: Option<GeoError> = None;
+
+    for provider in candidates {
+        match provider.locate(&payload, client_ip).await {
+            Ok(response) => {
+                #[cfg(feature = "elevation")]
+                let response = {
+                    let mut response = response;
+                    if let Some(service) = &config.elevation_service {
+                        match service.elevation_at(response.lat, response.lon).await {
+                            Ok(elevation) => response.elevation = elevation,
+                            Err(e) => error!(%ip, error = %e, "elevation lookup failed"),
+                        }
+                    }
+                    response
+                };
+
+                cache_store.insert(cache_key, response.clone()).await;
+
+                if let Err(e) = config
+                    .location_store
+                    .record(LocationPoint::new(ip.clone(), &response, now))
+                    .await
+                {
+                    error!(%ip, error = %e, "failed to persist location history");
+                }
+
+                return Ok(Json(response));
+            }
+            Err(e @ (GeoError::GoogleApi(_) | GeoError::Internal(_))) => {
+                error!(%ip, error = %e, "provider failed, trying next");
+                last_err = Some(e);
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
 
-        Ok(Json(response))
-    } else {
-        let status = resp.status();
-        error!(%ip, ?status, "Google API error");
-        Err(GeoError::GoogleApi(format!("{}", status)).into())
+    Err(last_err
+        .unwrap_or_else(|| GeoError::Internal("no geolocation providers configured".to_string()))
+        .into())
+}
+
+async fn handle_history(
+    Extension(config): Extension<AppConfig>,
+    Path(ip): Path<String>,
+    Query(query): Query<HistoryQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<LocationPoint>>, (StatusCode, String)> {
+    let principal = config
+        .auth
+        .authenticate(&headers)
+        .await
+        .map_err(Into::<(StatusCode, String)>::into)?;
+
+    if matches!(principal.identity, Identity::Anonymous) {
+        return Err(
+            GeoError::Unauthorized("location history requires an API key".to_string()).into(),
+        );
     }
+
+    let since = query
+        .since
+        .as_deref()
+        .map(parse_flexible_timestamp)
+        .transpose()
+        .map_err(Into::<(StatusCode, String)>::into)?;
+
+    let until = query
+        .until
+        .as_deref()
+        .map(parse_flexible_timestamp)
+        .transpose()
+        .map_err(Into::<(StatusCode, String)>::into)?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+
+    let points = config
+        .location_store
+        .history(&ip, since, until, limit)
+        .await
+        .map_err(Into::<(StatusCode, String)>::into)?;
+
+    Ok(Json(points))
 }
 
 #[tokio::main]
@@ -176,24 +283,89 @@ async fn main() {
         .parse()
         .unwrap_or(2);
 
+    let max_cache_entries: u64 = env::var("MAX_CACHE_ENTRIES")
+        .unwrap_or_else(|_| "10000".to_string())
+        .parse()
+        .unwrap_or(10000);
+
+    let max_body_bytes: usize = env::var("MAX_BODY_BYTES")
+        .unwrap_or_else(|_| "65536".to_string())
+        .parse()
+        .unwrap_or(65536);
+
+    let max_wifi_access_points: usize = env::var("MAX_WIFI_ACCESS_POINTS")
+        .unwrap_or_else(|_| "100".to_string())
+        .parse()
+        .unwrap_or(100);
+
+    let trusted_proxies = env::var("TRUSTED_PROXIES")
+        .map(|raw| parse_trusted_proxies(&raw))
+        .unwrap_or_default();
+
     let google_api_key =
         env::var("GOOGLE_API_KEY").expect("GOOGLE_API_KEY must be set in .env");
 
+    let mut providers: Vec<Arc<dyn GeoProvider>> = vec![Arc::new(GoogleProvider::new(google_api_key))];
+
+    if let Ok(ip_geo_api_key) = env::var("IPGEOLOCATION_API_KEY") {
+        providers.push(Arc::new(IpGeoProvider::new(ip_geo_api_key)));
+    } else {
+        info!("IPGEOLOCATION_API_KEY not set, IP-based fallback disabled");
+    }
+
+    let location_store: Arc<dyn LocationStore> = match env::var("DATABASE_URL") {
+        Ok(database_url) => Arc::new(
+            SqlLocationStore::connect(&database_url)
+                .await
+                .expect("failed to connect to DATABASE_URL"),
+        ),
+        Err(_) => Arc::new(MemoryLocationStore::new()),
+    };
+
+    let api_keys = env::var("API_KEYS")
+        .map(|raw| auth::parse_api_keys(&raw))
+        .unwrap_or_default();
+    let auth: Arc<dyn ApiAuth> = Arc::new(ApiKeyAuth::new(api_keys, max_requests_per_day));
+
+    let cache_ttl = Duration::hours(cache_ttl_hours);
+
+    #[cfg(feature = "elevation")]
+    let elevation_service = match env::var("DEM_PATH") {
+        Ok(dem_path) => match elevation::ElevationService::open(&dem_path) {
+            Ok(service) => Some(Arc::new(service)),
+            Err(e) => {
+                error!(error = %e, "failed to open DEM, elevation disabled");
+                None
+            }
+        },
+        Err(_) => None,
+    };
+
     let config = AppConfig {
-        cache_ttl: Duration::hours(cache_ttl_hours),
-        max_requests_per_day,
-        google_api_key,
+        max_wifi_access_points,
+        trusted_proxies,
+        providers,
+        location_store,
+        auth,
+        #[cfg(feature = "elevation")]
+        elevation_service,
     };
 
     let rate_limit_store: RateLimitStore = Arc::new(DashMap::new());
-    let cache_store: CacheStore = Arc::new(DashMap::new());
+    let cache_store: CacheStore = build_cache_store(
+        max_cache_entries,
+        cache_ttl.to_std().expect("cache TTL must be non-negative"),
+    );
 
     let app = Router::new()
         .route("/geo", post(handle_geo))
+        .route("/geo/:ip/history", get(handle_history))
         .layer(Extension(config))
         .layer(Extension(rate_limit_store))
         .layer(Extension(cache_store))
-        .layer(TraceLayer::new_for_http());
+        .layer(TraceLayer::new_for_http())
+        .layer(CompressionLayer::new())
+        .layer(RequestBodyLimitLayer::new(max_body_bytes));
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
         .await
@@ -0,0 +1,123 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::http::{header::FORWARDED, HeaderMap};
+
+/// Resolves the true client IP, honoring `X-Forwarded-For`/`Forwarded` only
+/// when the immediate peer (`peer`) is a configured trusted proxy. This
+/// keeps rate limiting and caching keyed on the real client rather than a
+/// header any untrusted caller could forge.
+pub fn resolve_client_ip(peer: SocketAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer.ip()) {
+        return peer.ip();
+    }
+
+    x_forwarded_for(headers)
+        .or_else(|| forwarded_for(headers))
+        .unwrap_or_else(|| peer.ip())
+}
+
+fn x_forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    // Proxies append their own peer's address to the end of this header, so
+    // the rightmost entry is the one our trusted proxy actually observed.
+    // Anything to the left of it can be forged by the original client.
+    headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next_back())
+        .map(str::trim)
+        .and_then(|s| s.parse().ok())
+}
+
+fn forwarded_for(headers: &HeaderMap) -> Option<IpAddr> {
+    // Same rationale as `x_forwarded_for`: a `Forwarded` header is a
+    // comma-separated list of forwarded-elements, one per hop, so the
+    // rightmost element is the one our trusted proxy actually observed.
+    let value = headers.get(FORWARDED)?.to_str().ok()?;
+    let last_element = value.split(',').next_back()?;
+
+    last_element.split(';').find_map(|part| {
+        let rest = part.trim().strip_prefix("for=")?;
+        rest.trim_matches('"').parse().ok()
+    })
+}
+
+/// Parses the `TRUSTED_PROXIES` env var: a comma-separated list of IPs.
+pub fn parse_trusted_proxies(raw: &str) -> Vec<IpAddr> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn peer(ip: &str) -> SocketAddr {
+        SocketAddr::new(ip.parse().unwrap(), 12345)
+    }
+
+    #[test]
+    fn untrusted_peer_is_used_directly_even_with_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_static("9.9.9.9"));
+
+        let resolved = resolve_client_ip(peer("1.2.3.4"), &headers, &[]);
+
+        assert_eq!(resolved, "1.2.3.4".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn trusted_peer_takes_rightmost_x_forwarded_for_entry() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("9.9.9.9, 8.8.8.8"),
+        );
+        let trusted = vec!["1.2.3.4".parse().unwrap()];
+
+        let resolved = resolve_client_ip(peer("1.2.3.4"), &headers, &trusted);
+
+        assert_eq!(resolved, "8.8.8.8".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn trusted_peer_takes_rightmost_forwarded_element() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            FORWARDED,
+            HeaderValue::from_static("for=9.9.9.9;proto=http, for=8.8.8.8"),
+        );
+        let trusted = vec!["1.2.3.4".parse().unwrap()];
+
+        let resolved = resolve_client_ip(peer("1.2.3.4"), &headers, &trusted);
+
+        assert_eq!(resolved, "8.8.8.8".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn trusted_peer_with_no_forwarding_headers_falls_back_to_peer() {
+        let headers = HeaderMap::new();
+        let trusted = vec!["1.2.3.4".parse().unwrap()];
+
+        let resolved = resolve_client_ip(peer("1.2.3.4"), &headers, &trusted);
+
+        assert_eq!(resolved, "1.2.3.4".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn parses_trusted_proxies_list() {
+        let parsed = parse_trusted_proxies(" 1.2.3.4, 5.6.7.8 ,,");
+
+        assert_eq!(
+            parsed,
+            vec![
+                "1.2.3.4".parse::<IpAddr>().unwrap(),
+                "5.6.7.8".parse::<IpAddr>().unwrap(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::net::IpAddr;
+use tracing::{error, info};
+
+use super::GeoProvider;
+use crate::{GeoError, GeoRequest, LocationResponse};
+
+#[derive(Debug, Deserialize)]
+struct GoogleGeoResponse {
+    location: GoogleLocation,
+    accuracy: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleLocation {
+    lat: f64,
+    lng: f64,
+}
+
+/// Resolves WiFi access points via Google's `geolocation/v1/geolocate` API.
+pub struct GoogleProvider {
+    api_key: String,
+    client: Client,
+}
+
+impl GoogleProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl GeoProvider for GoogleProvider {
+    async fn locate(&self, req: &GeoRequest, _client_ip: IpAddr) -> Result<LocationResponse, GeoError> {
+        let url = format!(
+            "https://www.googleapis.com/geolocation/v1/geolocate?key={}",
+            self.api_key
+        );
+
+        let resp = self.client.post(&url).json(req).send().await.map_err(|e| {
+            error!(error = ?e, "google geolocation request failed");
+            GeoError::Internal(e.to_string())
+        })?;
+
+        if resp.status().is_success() {
+            let geo: GoogleGeoResponse = resp.json().await.map_err(|e| {
+                error!(error = ?e, "google geolocation json decode failed");
+                GeoError::Internal(e.to_string())
+            })?;
+
+            info!(lat = geo.location.lat, lon = geo.location.lng, accuracy = geo.accuracy, "google geolocation success");
+
+            Ok(LocationResponse {
+                lat: geo.location.lat,
+                lon: geo.location.lng,
+                accuracy: Some(geo.accuracy),
+                elevation: None,
+            })
+        } else {
+            let status = resp.status();
+            error!(?status, "google geolocation api error");
+            Err(GeoError::GoogleApi(format!("{}", status)))
+        }
+    }
+}
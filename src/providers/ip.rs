@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use std::net::IpAddr;
+use tracing::{error, info};
+
+use super::GeoProvider;
+use crate::{GeoError, GeoRequest, LocationResponse};
+
+#[derive(Debug, Deserialize)]
+struct IpGeoResponse {
+    latitude: String,
+    longitude: String,
+}
+
+/// Resolves a location purely from the client's IP address via
+/// ipgeolocation.io. Used as a fallback when WiFi-based lookups fail or
+/// aren't available.
+pub struct IpGeoProvider {
+    api_key: String,
+    client: Client,
+}
+
+impl IpGeoProvider {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            api_key,
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl GeoProvider for IpGeoProvider {
+    async fn locate(&self, _req: &GeoRequest, client_ip: IpAddr) -> Result<LocationResponse, GeoError> {
+        let url = format!(
+            "https://api.ipgeolocation.io/ipgeo?apiKey={}&ip={}&fields=latitude,longitude",
+            self.api_key, client_ip
+        );
+
+        let resp = self.client.get(&url).send().await.map_err(|e| {
+            error!(%client_ip, error = ?e, "ip geolocation request failed");
+            GeoError::Internal(e.to_string())
+        })?;
+
+        if resp.status().is_success() {
+            let geo: IpGeoResponse = resp.json().await.map_err(|e| {
+                error!(%client_ip, error = ?e, "ip geolocation json decode failed");
+                GeoError::Internal(e.to_string())
+            })?;
+
+            let lat: f64 = geo
+                .latitude
+                .parse()
+                .map_err(|e| GeoError::Internal(format!("invalid latitude from ip provider: {e}")))?;
+            let lon: f64 = geo
+                .longitude
+                .parse()
+                .map_err(|e| GeoError::Internal(format!("invalid longitude from ip provider: {e}")))?;
+
+            info!(%client_ip, lat, lon, "ip geolocation success");
+
+            Ok(LocationResponse {
+                lat,
+                lon,
+                accuracy: None,
+                elevation: None,
+            })
+        } else {
+            let status = resp.status();
+            error!(%client_ip, ?status, "ip geolocation api error");
+            Err(GeoError::GoogleApi(format!("{}", status)))
+        }
+    }
+
+    fn requires_wifi(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,25 @@
+mod google;
+mod ip;
+
+pub use google::GoogleProvider;
+pub use ip::IpGeoProvider;
+
+use async_trait::async_trait;
+use std::net::IpAddr;
+
+use crate::{GeoError, GeoRequest, LocationResponse};
+
+/// A backend capable of resolving a [`GeoRequest`] (plus the caller's IP) into
+/// a [`LocationResponse`]. Implementations are tried in order by `handle_geo`,
+/// so a failing/unreachable provider can fall through to the next one.
+#[async_trait]
+pub trait GeoProvider: Send + Sync {
+    async fn locate(&self, req: &GeoRequest, client_ip: IpAddr) -> Result<LocationResponse, GeoError>;
+
+    /// Whether this provider needs `wifi_access_points` to produce a result.
+    /// IP-based providers don't, and are the only ones considered when the
+    /// request arrives with no access points.
+    fn requires_wifi(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+
+use super::{LocationPoint, LocationStore};
+use crate::GeoError;
+
+/// In-memory [`LocationStore`] backed by a `DashMap<ip, Vec<LocationPoint>>`.
+/// Simple and fast, but history is lost on restart and unbounded per IP.
+#[derive(Default)]
+pub struct MemoryLocationStore {
+    points: DashMap<String, Vec<LocationPoint>>,
+}
+
+impl MemoryLocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LocationStore for MemoryLocationStore {
+    async fn record(&self, point: LocationPoint) -> Result<(), GeoError> {
+        self.points.entry(point.ip.clone()).or_default().push(point);
+        Ok(())
+    }
+
+    async fn history(
+        &self,
+        ip: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<LocationPoint>, GeoError> {
+        let mut points = self
+            .points
+            .get(ip)
+            .map(|entries| entries.clone())
+            .unwrap_or_default();
+
+        points.retain(|p| {
+            since.is_none_or(|s| p.recorded_at >= s) && until.is_none_or(|u| p.recorded_at <= u)
+        });
+
+        points.sort_by_key(|p| std::cmp::Reverse(p.recorded_at));
+        points.truncate(limit);
+
+        Ok(points)
+    }
+}
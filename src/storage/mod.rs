@@ -0,0 +1,94 @@
+mod memory;
+mod sql;
+
+pub use memory::MemoryLocationStore;
+pub use sql::SqlLocationStore;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+
+use crate::{GeoError, LocationResponse};
+
+/// A single recorded lookup, returned newest-first from `history`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LocationPoint {
+    pub ip: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub accuracy: Option<f64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl LocationPoint {
+    pub fn new(ip: String, response: &LocationResponse, recorded_at: DateTime<Utc>) -> Self {
+        Self {
+            ip,
+            lat: response.lat,
+            lon: response.lon,
+            accuracy: response.accuracy,
+            recorded_at,
+        }
+    }
+}
+
+/// Persists successful geolocation lookups so a client's location history can
+/// be queried later. The DashMap-backed [`MemoryLocationStore`] and the
+/// [`SqlLocationStore`] are interchangeable behind this trait.
+#[async_trait]
+pub trait LocationStore: Send + Sync {
+    async fn record(&self, point: LocationPoint) -> Result<(), GeoError>;
+
+    async fn history(
+        &self,
+        ip: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<LocationPoint>, GeoError>;
+}
+
+/// Timestamp query params accept a few common formats, tried in order:
+/// RFC 3339 with an offset, RFC 3339 with a literal `Z`, and a bare
+/// `YYYY-MM-DD HH:MM:SS` (assumed UTC).
+pub fn parse_flexible_timestamp(raw: &str) -> Result<DateTime<Utc>, GeoError> {
+    if let Ok(dt) = DateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f%:z") {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.fZ") {
+        return Ok(DateTime::from_naive_utc_and_offset(naive, Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f") {
+        return Ok(DateTime::from_naive_utc_and_offset(naive, Utc));
+    }
+
+    Err(GeoError::Internal(format!("unrecognized timestamp: {raw}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rfc3339_with_offset() {
+        let parsed = parse_flexible_timestamp("2024-01-02T03:04:05.5+02:00").unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-01-02T01:04:05.500+00:00");
+    }
+
+    #[test]
+    fn parses_rfc3339_with_literal_z() {
+        let parsed = parse_flexible_timestamp("2024-01-02T03:04:05Z").unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    }
+
+    #[test]
+    fn parses_bare_space_separated_timestamp() {
+        let parsed = parse_flexible_timestamp("2024-01-02 03:04:05").unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2024-01-02T03:04:05+00:00");
+    }
+
+    #[test]
+    fn rejects_unrecognized_format() {
+        assert!(parse_flexible_timestamp("not a timestamp").is_err());
+    }
+}
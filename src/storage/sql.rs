@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use super::{LocationPoint, LocationStore};
+use crate::GeoError;
+
+/// Postgres-backed [`LocationStore`]. Expects a `location_history` table:
+///
+/// ```sql
+/// CREATE TABLE location_history (
+///     ip          TEXT NOT NULL,
+///     lat         DOUBLE PRECISION NOT NULL,
+///     lon         DOUBLE PRECISION NOT NULL,
+///     accuracy    DOUBLE PRECISION,
+///     recorded_at TIMESTAMPTZ NOT NULL
+/// );
+/// ```
+pub struct SqlLocationStore {
+    pool: PgPool,
+}
+
+impl SqlLocationStore {
+    pub async fn connect(database_url: &str) -> Result<Self, GeoError> {
+        let pool = PgPool::connect(database_url)
+            .await
+            .map_err(|e| GeoError::Internal(format!("failed to connect to database: {e}")))?;
+
+        Ok(Self { pool })
+    }
+}
+
+#[async_trait]
+impl LocationStore for SqlLocationStore {
+    async fn record(&self, point: LocationPoint) -> Result<(), GeoError> {
+        sqlx::query(
+            "INSERT INTO location_history (ip, lat, lon, accuracy, recorded_at) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&point.ip)
+        .bind(point.lat)
+        .bind(point.lon)
+        .bind(point.accuracy)
+        .bind(point.recorded_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| GeoError::Internal(format!("failed to record location: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn history(
+        &self,
+        ip: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<LocationPoint>, GeoError> {
+        let rows = sqlx::query_as::<_, (String, f64, f64, Option<f64>, DateTime<Utc>)>(
+            "SELECT ip, lat, lon, accuracy, recorded_at FROM location_history \
+             WHERE ip = $1 \
+               AND ($2::timestamptz IS NULL OR recorded_at >= $2) \
+               AND ($3::timestamptz IS NULL OR recorded_at <= $3) \
+             ORDER BY recorded_at DESC \
+             LIMIT $4",
+        )
+        .bind(ip)
+        .bind(since)
+        .bind(until)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| GeoError::Internal(format!("failed to query location history: {e}")))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(ip, lat, lon, accuracy, recorded_at)| LocationPoint {
+                ip,
+                lat,
+                lon,
+                accuracy,
+                recorded_at,
+            })
+            .collect())
+    }
+}